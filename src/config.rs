@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Per-variant weights steering [`Command`](crate::simulator::Command) generation.
+///
+/// The weights are relative: a `write` of 4 against a `read` of 1 makes writes four times as
+/// likely. This is what lets a user bias a session toward the memory path or toward the
+/// arithmetic path when hunting overflow bugs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    pub read: u32,
+    pub write: u32,
+    pub sum: u32,
+    pub product: u32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        // Uniform, matching the original `g.choose` behaviour.
+        Self {
+            read: 1,
+            write: 1,
+            sum: 1,
+            product: 1,
+        }
+    }
+}
+
+/// Run-time configuration for a fuzzing session.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Address of the remote under test.
+    pub remote_addr: String,
+    /// Number of bytes in the simulator's memory.
+    pub memory_size: usize,
+    /// Number of concurrent worker connections to open against the remote.
+    pub connections: usize,
+    /// Upper bound on the number of commands each worker generates before giving up.
+    pub max_iterations: usize,
+    /// Optional RNG seed; when set a session can be rerun identically.
+    pub seed: Option<u64>,
+    /// Per-variant generation weights.
+    pub weights: Weights,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            remote_addr: String::from("127.0.0.1:10203"),
+            memory_size: 4,
+            connections: 1,
+            max_iterations: 100_000,
+            seed: None,
+            weights: Weights::default(),
+        }
+    }
+}
+
+/// Errors raised while loading a [`Config`] from disk.
+#[derive(Debug)]
+pub enum Error {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file was not valid TOML.
+    Parse(toml::de::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(value: toml::de::Error) -> Self {
+        Error::Parse(value)
+    }
+}
+
+impl Config {
+    /// Load a [`Config`] from a TOML file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+}
@@ -0,0 +1,85 @@
+use log::warn;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+
+use crate::{config::Config, simulator::Command};
+
+/// Weighted, optionally-seeded generator of [`Command`]s.
+///
+/// Variant selection, location indices and byte payloads all flow through a single seeded
+/// [`StdRng`], so a session can be reproduced identically from a fixed seed.
+pub struct Generator {
+    rng: StdRng,
+    dist: WeightedIndex<u32>,
+    /// Largest location index generated: `memory_size`, i.e. one past the last valid byte,
+    /// saturated at [`u8::MAX`] (see [`max_index`]).
+    max_index: u8,
+}
+
+impl Generator {
+    /// Build a [`Generator`] from the session [`Config`].
+    pub fn new(config: &Config) -> Self {
+        Self::with_worker(config, 0)
+    }
+
+    /// Build a [`Generator`] for worker `worker`.
+    ///
+    /// The seed is offset by the worker index so concurrent workers explore independent traces
+    /// while a session still replays identically for a given base seed.
+    pub fn with_worker(config: &Config, worker: usize) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(worker as u64)),
+            None => StdRng::from_entropy(),
+        };
+        let weights = &config.weights;
+        // Order matters: it mirrors the match in `next_command`.
+        let dist = WeightedIndex::new([
+            weights.read,
+            weights.write,
+            weights.sum,
+            weights.product,
+        ])
+        .expect("at least one command weight must be non-zero");
+        Self {
+            rng,
+            dist,
+            max_index: max_index(config.memory_size),
+        }
+    }
+
+    /// Generate the next [`Command`] according to the configured weights.
+    pub fn next_command(&mut self) -> Command {
+        match self.dist.sample(&mut self.rng) {
+            0 => Command::Read(self.location()),
+            1 => Command::Write(self.location(), self.rng.gen::<u8>()),
+            2 => Command::Sum,
+            _ => Command::Product,
+        }
+    }
+
+    /// Pick a location ranging over valid and one-past-valid indices.
+    fn location(&mut self) -> u8 {
+        self.rng.gen_range(0..=self.max_index)
+    }
+}
+
+/// Largest location index to generate for a `memory_size`-byte simulator.
+///
+/// Locations are a `u8`, so we cannot express `memory_size` (the one-past-valid index) once the
+/// memory reaches 256 bytes. Rather than silently truncate we saturate at [`u8::MAX`] and warn,
+/// so large address spaces still probe their top byte instead of collapsing to index 0.
+fn max_index(memory_size: usize) -> u8 {
+    if memory_size > u8::MAX as usize {
+        warn!(
+            "memory_size {} exceeds the u8 location range; clamping generated indices to {}",
+            memory_size,
+            u8::MAX
+        );
+        u8::MAX
+    } else {
+        memory_size as u8
+    }
+}
@@ -1,16 +1,30 @@
+mod config;
+mod generate;
+mod replay;
+mod repl;
 mod simulator;
 
 use std::{
     io::{Read, Write},
     net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
 };
 
 use env_logger::Env;
 use log::{debug, error, info, trace};
-use quickcheck::Arbitrary;
 use simulator::Command;
 
-use crate::simulator::Simulator;
+use crate::{config::Config, generate::Generator, replay::Trace, simulator::Simulator};
+
+/// Default path a failing trace is persisted to.
+const TRACE_PATH: &str = "trace.cbor";
+
+/// Default path the session configuration is read from.
+const CONFIG_PATH: &str = "config.toml";
 
 /// Response error.
 #[derive(Debug)]
@@ -90,33 +104,212 @@ fn execute_command(
     Ok(())
 }
 
-fn main() {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+/// Replay `candidate` from scratch and report whether it still reproduces the divergence.
+///
+/// The simulator is stateful, so a fresh [`Simulator`] is built for every trial; likewise the
+/// remote socket is re-opened each time because a panicked remote closes the connection and a
+/// failed re-connect is itself a communication error we want to treat as a reproduction.
+fn reproduces(candidate: &[Command], addr: &str, memory_size: usize) -> bool {
+    let mut simulator = Simulator::new(memory_size);
+    let mut stream = match TcpStream::connect(addr) {
+        Ok(stream) => stream,
+        // The remote is unreachable, which counts as a communication error.
+        Err(_) => return true,
+    };
 
-    let mut simulator = Simulator::new();
+    for command in candidate {
+        if execute_command(&mut simulator, &mut stream, command).is_err() {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Reduce a failing `trace` to a locally-minimal subsequence that still reproduces the divergence.
+///
+/// This is the classic ddmin delta-debugging algorithm: the trace is split into `n` roughly equal
+/// chunks and we test whether removing any single chunk still reproduces; a successful removal is
+/// adopted and the granularity is relaxed, otherwise the granularity is doubled. We stop once no
+/// removal helps at the finest granularity.
+fn minimize(trace: &[Command], addr: &str, memory_size: usize) -> Vec<Command> {
+    let mut trace = trace.to_vec();
+    let mut n = 2;
+
+    while trace.len() >= 2 {
+        // Ceiling division so the last chunk picks up any remainder.
+        let chunk_size = trace.len().div_ceil(n);
+        let mut reduced = false;
+
+        for chunk in 0..n {
+            let start = chunk * chunk_size;
+            if start >= trace.len() {
+                break;
+            }
+            let end = (start + chunk_size).min(trace.len());
+
+            let mut complement = trace.clone();
+            complement.drain(start..end);
+
+            if reproduces(&complement, addr, memory_size) {
+                trace = complement;
+                n = (n - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced {
+            if n >= trace.len() {
+                break;
+            }
+            n = (n * 2).min(trace.len());
+        }
+    }
+
+    trace
+}
+
+/// Re-run a previously recorded `trace` step by step against the remote.
+///
+/// Unlike the fuzzing loop this consumes fixed [`Command`]s instead of drawing fresh ones from a
+/// [`Generator`], which makes it a deterministic regression check for a
+/// counterexample against a (hopefully) patched remote.
+fn replay_trace(trace: &Trace, addr: &str, memory_size: usize) {
+    let mut simulator = Simulator::new(memory_size);
     trace!("initialized simulator");
 
-    let mut stream =
-        TcpStream::connect("127.0.0.1:10203").expect("connection should be successful");
+    let mut stream = TcpStream::connect(addr).expect("connection should be successful");
     trace!("opened connection");
 
-    let mut g = quickcheck::Gen::new(256);
-    let mut trace: Vec<Command> = vec![];
+    for command in &trace.0 {
+        debug!("replaying command: {:?}", command);
+        if execute_command(&mut simulator, &mut stream, command).is_err() {
+            error!("replay reproduced the divergence");
+            return;
+        }
+    }
+
+    info!("replay completed without divergence ({} commands)", trace.0.len());
+}
 
-    loop {
-        let command = Command::arbitrary(&mut g);
-        debug!("generated command: {:?}", command);
-        trace.push(command);
+/// Run `config.connections` workers concurrently, returning the first reported divergence.
+///
+/// Each worker owns its own [`Simulator`] and [`TcpStream`] and generates an independent trace; on
+/// the first `Err(())` a worker signals the others to stop and reports `(worker, trace)` back over
+/// an mpsc channel. Returns `None` when no worker reports a divergence, i.e. each worker either
+/// exhausts `config.max_iterations` cleanly or fails to connect.
+fn fuzz(config: &Config) -> Option<(usize, Vec<Command>)> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
 
-        let command = trace
-            .last()
-            .expect("the command was just pushed, it should be in the vector's last position");
+    let mut workers = Vec::with_capacity(config.connections);
+    for worker in 0..config.connections {
+        let config = config.clone();
+        let stop = Arc::clone(&stop);
+        let tx = tx.clone();
+        workers.push(thread::spawn(move || {
+            let mut simulator = Simulator::new(config.memory_size);
+            let mut stream = match TcpStream::connect(&config.remote_addr) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("worker {} failed to connect: {}", worker, e);
+                    return;
+                }
+            };
+            let mut generator = Generator::with_worker(&config, worker);
+            let mut trace: Vec<Command> = vec![];
 
-        if execute_command(&mut simulator, &mut stream, command).is_err() {
-            break;
+            for _ in 0..config.max_iterations {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                let command = generator.next_command();
+                trace.push(command);
+                let command = trace
+                    .last()
+                    .expect("the command was just pushed, it should be in the vector's last position");
+
+                if execute_command(&mut simulator, &mut stream, command).is_err() {
+                    stop.store(true, Ordering::Relaxed);
+                    // A send failure just means the aggregator already has a result.
+                    let _ = tx.send((worker, trace));
+                    return;
+                }
+            }
+        }));
+    }
+    // Drop our own sender so `recv` returns `Err` once every worker is done.
+    drop(tx);
+
+    let first = rx.recv().ok();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    first
+}
+
+fn main() {
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let config = match Config::from_file(CONFIG_PATH) {
+        Ok(config) => config,
+        // An absent config is fine — fall back to defaults.
+        Err(config::Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            info!("no {}, falling back to defaults", CONFIG_PATH);
+            Config::default()
+        }
+        // A present-but-broken config must not be silently ignored: reproducibility and
+        // weighting correctness depend on it actually being applied.
+        Err(e) => {
+            error!("failed to load {}: {:?}", CONFIG_PATH, e);
+            std::process::exit(1);
+        }
+    };
+    debug!("loaded config: {:?}", config);
+
+    let mut args = std::env::args().skip(1);
+    if let Some(flag) = args.next() {
+        if flag == "--replay" {
+            let path = args
+                .next()
+                .expect("--replay requires a path to a serialized trace");
+            let trace = Trace::read_from_file(&path).expect("trace should deserialize");
+            trace!("loaded trace from {}", path);
+            replay_trace(&trace, &config.remote_addr, config.memory_size);
+            return;
+        }
+        if flag == "--interactive" {
+            repl::run(&config);
+            return;
         }
     }
 
-    info!("number of commands processed: {}", trace.len());
+    info!("spawning {} worker(s)", config.connections);
+    let (worker, trace) = match fuzz(&config) {
+        Some(result) => result,
+        None => {
+            info!("no divergence found");
+            return;
+        }
+    };
+
+    error!("worker {} diverged after {} commands", worker, trace.len());
     debug!("command trace: {:?}", trace);
+
+    let recorded = Trace(trace.clone());
+    if let Err(e) = recorded.write_to_file(TRACE_PATH) {
+        error!("failed to persist failing trace: {}", e);
+    } else {
+        info!("persisted failing trace to {}", TRACE_PATH);
+    }
+
+    let minimized = minimize(&trace, &config.remote_addr, config.memory_size);
+    error!(
+        "minimized failing trace ({} commands): {:?}",
+        minimized.len(),
+        minimized
+    );
+    info!("minimized from {} to {} commands", trace.len(), minimized.len());
 }
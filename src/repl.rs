@@ -0,0 +1,136 @@
+use std::{
+    io::{self, BufRead, Write},
+    net::TcpStream,
+};
+
+use log::{error, info};
+
+use crate::{
+    config::Config,
+    execute_command,
+    generate::Generator,
+    simulator::{Command, Simulator},
+};
+
+/// A parsed line of REPL input: either a [`Command`] to run or a meta-command.
+enum Input {
+    /// Run a command through [`execute_command`].
+    Command(Command),
+    /// Print the simulator's current memory.
+    Dump,
+    /// Pop the last command and rebuild simulator state by replaying.
+    Undo,
+    /// Generate and apply `n` random commands.
+    Run(usize),
+    /// Leave the loop.
+    Quit,
+}
+
+/// Parse a single line of input, returning a human-readable error on malformed input.
+fn parse(line: &str) -> Result<Input, String> {
+    let mut tokens = line.split_whitespace();
+    let head = tokens.next().ok_or_else(|| String::from("empty input"))?;
+
+    // Small helper to parse the next token as some `FromStr` type.
+    fn arg<T: std::str::FromStr>(
+        tokens: &mut std::str::SplitWhitespace,
+        what: &str,
+    ) -> Result<T, String> {
+        tokens
+            .next()
+            .ok_or_else(|| format!("missing {}", what))?
+            .parse()
+            .map_err(|_| format!("invalid {}", what))
+    }
+
+    match head {
+        "read" => Ok(Input::Command(Command::Read(arg(&mut tokens, "location")?))),
+        "write" => Ok(Input::Command(Command::Write(
+            arg(&mut tokens, "location")?,
+            arg(&mut tokens, "value")?,
+        ))),
+        "sum" => Ok(Input::Command(Command::Sum)),
+        "product" => Ok(Input::Command(Command::Product)),
+        "dump" => Ok(Input::Dump),
+        "undo" => Ok(Input::Undo),
+        "run" => Ok(Input::Run(arg(&mut tokens, "count")?)),
+        "quit" | "exit" => Ok(Input::Quit),
+        other => Err(format!("unknown command: {}", other)),
+    }
+}
+
+/// Run the interactive read-eval-print loop.
+///
+/// Each entered command is threaded through [`execute_command`] so divergences are reported
+/// inline; `undo` rebuilds the local [`Simulator`] from scratch (the remote cannot be rewound).
+pub fn run(config: &Config) {
+    let mut simulator = Simulator::new(config.memory_size);
+    let mut stream =
+        TcpStream::connect(&config.remote_addr).expect("connection should be successful");
+    let mut generator = Generator::new(config);
+    let mut trace: Vec<Command> = vec![];
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            // EOF (Ctrl-D) ends the session.
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                error!("failed to read input: {}", e);
+                break;
+            }
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse(line) {
+            Ok(Input::Command(command)) => apply(&mut simulator, &mut stream, &mut trace, command),
+            Ok(Input::Dump) => println!("memory: {:?}", simulator.memory()),
+            Ok(Input::Undo) => {
+                if trace.pop().is_none() {
+                    println!("nothing to undo");
+                    continue;
+                }
+                // Rebuild local state by replaying the remaining trace from scratch.
+                simulator = Simulator::new(config.memory_size);
+                for command in &trace {
+                    let _ = simulator.execute_command(command);
+                }
+                info!("undone, {} commands remain", trace.len());
+            }
+            Ok(Input::Run(n)) => {
+                for _ in 0..n {
+                    let command = generator.next_command();
+                    apply(&mut simulator, &mut stream, &mut trace, command);
+                }
+            }
+            Ok(Input::Quit) => break,
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
+
+/// Push `command` onto the trace, execute it, and report a divergence inline.
+fn apply(
+    simulator: &mut Simulator,
+    stream: &mut TcpStream,
+    trace: &mut Vec<Command>,
+    command: Command,
+) {
+    trace.push(command);
+    let command = trace
+        .last()
+        .expect("the command was just pushed, it should be in the vector's last position");
+
+    if execute_command(simulator, stream, command).is_err() {
+        error!("divergence at command {}: {:?}", trace.len(), command);
+    }
+}
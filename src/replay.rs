@@ -0,0 +1,26 @@
+use std::{fs::File, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulator::Command;
+
+/// A recorded sequence of commands that reproduced a divergence.
+///
+/// Wrapping the `Vec<Command>` gives the on-disk format a stable, self-describing shape and a
+/// natural home for the (de)serialization helpers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Trace(pub Vec<Command>);
+
+impl Trace {
+    /// Serialize the trace to `path` using CBOR.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), serde_cbor::Error> {
+        let file = File::create(path)?;
+        serde_cbor::to_writer(file, self)
+    }
+
+    /// Deserialize a previously written trace from `path`.
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, serde_cbor::Error> {
+        let file = File::open(path)?;
+        serde_cbor::from_reader(file)
+    }
+}
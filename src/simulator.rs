@@ -1,4 +1,4 @@
-use quickcheck::Arbitrary;
+use serde::{Deserialize, Serialize};
 
 /// Simulator errors.
 #[derive(Debug)]
@@ -12,12 +12,17 @@ pub enum Error {
 }
 
 /// Simulator for the remote to detect discrepancies.
-pub struct Simulator([u8; 4]);
+pub struct Simulator(Vec<u8>);
 
 impl Simulator {
-    /// Create a new [`Simulator`].
-    pub fn new() -> Self {
-        Self([0; 4])
+    /// Create a new [`Simulator`] with `size` bytes of zeroed memory.
+    pub fn new(size: usize) -> Self {
+        Self(vec![0; size])
+    }
+
+    /// Borrow the simulator's current memory contents.
+    pub fn memory(&self) -> &[u8] {
+        &self.0
     }
 
     /// Execute a [`Command`] on the [`Simulator`].
@@ -67,7 +72,7 @@ impl Simulator {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Command {
     /// Read a byte at the given index.
     Read(u8),
@@ -89,33 +94,3 @@ impl Command {
         }
     }
 }
-
-impl Arbitrary for Command {
-    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-        // Choosing 0 to 3 since 4 will panic the remote, we could add that but leads to not very interesting tests
-        // Typing is easier this way, hence the static
-        static CHOICES: [u8; 5] = [0, 1, 2, 3, 4];
-        fn read(g: &mut quickcheck::Gen) -> Command {
-            Command::Read(*(g.choose(&CHOICES).unwrap()))
-        }
-        fn write(g: &mut quickcheck::Gen) -> Command {
-            Command::Write(*(g.choose(&CHOICES).unwrap()), u8::arbitrary(g))
-        }
-        fn product(_: &mut quickcheck::Gen) -> Command {
-            Command::Product
-        }
-        fn sum(_: &mut quickcheck::Gen) -> Command {
-            Command::Sum
-        }
-        // This is a weird dialect at first sight but allows for lazyness when generating the cases
-        // furthermore, it's simpler when picking a branch because they're built on demand
-        g.choose(&[
-            read as fn(&mut quickcheck::Gen) -> Command,
-            write as fn(&mut quickcheck::Gen) -> Command,
-            product as fn(&mut quickcheck::Gen) -> Command,
-            sum as fn(&mut quickcheck::Gen) -> Command,
-        ])
-        // SAFETY: `choose` docs state that this will never be none if a non-empty slice is passed
-        .unwrap()(g)
-    }
-}